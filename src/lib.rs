@@ -9,20 +9,38 @@
 //! The current capabilities of Fonterator:
 //!
 //! * Reading TrueType formatted fonts and font collections. This includes
-//!   `*.ttf` as well as a subset of `*.otf` font files.
+//!   `*.ttf` as well as a subset of `*.otf` font files. WOFF and WOFF2 are
+//!   also accepted (behind the `"woff"`/`"woff2"` features respectively) and
+//!   are transparently decompressed into an SFNT buffer first; WOFF2 tables
+//!   using the transform encodings aren't reconstructed yet.
 //! * Retrieving glyph shapes and commonly used properties for a font and its
 //!   glyphs.
-//! * Laying out glyphs horizontally using horizontal and vertical metrics, and
-//!   glyph-pair-specific kerning.
+//! * Laying out glyphs horizontally using horizontal and vertical metrics,
+//!   glyph-pair-specific kerning, and `LayoutDir` for right-to-left or
+//!   top-to-bottom text. `TopToBottom` uses each glyph's own `vhea`/`vmtx`
+//!   advance height when the font has those tables, falling back to the
+//!   font's global line height otherwise.
+//! * Querying a font's family/PostScript name and its weight/slant `Style`,
+//!   and picking the closest face out of a `FontCollection` with
+//!   `FontCollection::best_match`.
+//! * `Font::shape`, which applies a font's `GSUB` single- and
+//!   ligature-substitution lookups for its default script/language's
+//!   default-on features (`ccmp`/`locl`/`rlig`/`calt`/`liga`/`clig`) before
+//!   laying a string out, so that sequences like "fi" become their ligature
+//!   glyph. Optional features (small caps, stylistic sets, ...) are left
+//!   alone.
 //!
 //! Notable things that Fonterator does not support *yet*:
 //!
 //! * OpenType formatted fonts that are not just TrueType fonts (OpenType is a
-//!   superset of TrueType). Notably there is no support yet for cubic Bezier
-//!   curves used in glyphs.
-//! * Ligatures of any kind (‽, etc.).
+//!   superset of TrueType). `PathOp` can represent the cubic Bezier curves
+//!   used by CFF-flavored glyphs, and `Path::into_quadratic` can flatten them
+//!   down, but the font parser itself does not yet read CFF outline data, so
+//!   no font source currently produces them.
+//! * GSUB lookup types other than single (1) and ligature (4) substitution,
+//!   such as contextual or chaining substitution, and any `GPOS` (glyph
+//!   positioning) feature.
 //! * Some less common TrueType sub-formats.
-//! * Right-to-left and vertical text layout.
 //!
 //! # Getting Started
 //!
@@ -76,6 +94,13 @@
 extern crate ordered_float;
 extern crate stb_truetype;
 extern crate unicode_normalization;
+// WOFF decompression needs zlib inflate, and WOFF2 needs Brotli; both are
+// optional so that consumers who only ever load raw TrueType/OpenType data
+// don't pay for decompressors they don't use.
+#[cfg(feature = "woff")]
+extern crate miniz_oxide;
+#[cfg(feature = "woff2")]
+extern crate brotli_decompressor;
 
 use unicode_normalization::UnicodeNormalization;
 
@@ -111,6 +136,129 @@ pub enum PathOp {
 	LineClose,
 	/// Close the path with a quadratic curve `cx, cy`
 	QuadClose(f32, f32),
+	/// Cubic curve `x, y, c1x, c1y, c2x, c2y`
+	CurveTo(f32, f32, f32, f32, f32, f32),
+	/// Close the path with a cubic curve `c1x, c1y, c2x, c2y`
+	CurveClose(f32, f32, f32, f32),
+}
+
+impl Path {
+	/// Flattens any cubic [`PathOp::CurveTo`]/[`PathOp::CurveClose`] in this
+	/// path into chains of [`PathOp::QuadTo`]/[`PathOp::QuadClose`], for
+	/// consumers (like the rasterizer, or the SVG example) that only
+	/// understand quadratic curves. `MoveTo`, `LineTo` and already-quadratic
+	/// ops pass through unchanged.
+	///
+	/// Each cubic segment is recursively subdivided at its midpoint until
+	/// its control points are within `tolerance` of the chord between its
+	/// endpoints, then approximated by a single quadratic sharing those
+	/// endpoints.
+	pub fn into_quadratic(self, tolerance: f32) -> Path {
+		let mut out = Vec::new();
+		let mut cur = Vec2(0.0, 0.0);
+		let mut start = Vec2(0.0, 0.0);
+
+		for op in self.0 {
+			match op {
+				PathOp::MoveTo(x, y) => {
+					cur = Vec2(x, y);
+					start = cur;
+					out.push(PathOp::MoveTo(x, y));
+				}
+				PathOp::LineTo(x, y) => {
+					cur = Vec2(x, y);
+					out.push(PathOp::LineTo(x, y));
+				}
+				PathOp::QuadTo(x, y, cx, cy) => {
+					cur = Vec2(x, y);
+					out.push(PathOp::QuadTo(x, y, cx, cy));
+				}
+				PathOp::LineClose => {
+					out.push(PathOp::LineClose);
+					cur = start;
+				}
+				PathOp::QuadClose(cx, cy) => {
+					out.push(PathOp::QuadClose(cx, cy));
+					cur = start;
+				}
+				PathOp::CurveTo(x, y, c1x, c1y, c2x, c2y) => {
+					let p1 = Vec2(x, y);
+					let mut quads = Vec::new();
+					flatten_cubic(cur, Vec2(c1x, c1y), Vec2(c2x, c2y), p1, tolerance, 0, &mut quads);
+					for (control, point) in quads {
+						out.push(PathOp::QuadTo(point.0, point.1, control.0, control.1));
+					}
+					cur = p1;
+				}
+				PathOp::CurveClose(c1x, c1y, c2x, c2y) => {
+					let mut quads = Vec::new();
+					flatten_cubic(cur, Vec2(c1x, c1y), Vec2(c2x, c2y), start, tolerance, 0, &mut quads);
+					let last = quads.len().saturating_sub(1);
+					for (i, (control, point)) in quads.into_iter().enumerate() {
+						if i == last {
+							out.push(PathOp::QuadClose(control.0, control.1));
+						} else {
+							out.push(PathOp::QuadTo(point.0, point.1, control.0, control.1));
+						}
+					}
+					cur = start;
+				}
+			}
+		}
+
+		Path(out)
+	}
+}
+
+// Recursion depth limit for cubic flattening, as a backstop against
+// near-degenerate curves that would never satisfy `tolerance`.
+const CUBIC_FLATTEN_MAX_DEPTH: u32 = 16;
+
+// Perpendicular distance from `p` to the line through `a` and `b`.
+fn point_line_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+	let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+	let len = (dx * dx + dy * dy).sqrt();
+	if len < f32::EPSILON {
+		return ((p.0 - a.0).powi(2) + (p.1 - a.1).powi(2)).sqrt();
+	}
+	((p.0 - a.0) * dy - (p.1 - a.1) * dx).abs() / len
+}
+
+fn cubic_is_flat(p0: Vec2, c1: Vec2, c2: Vec2, p1: Vec2, tolerance: f32) -> bool {
+	point_line_distance(c1, p0, p1) <= tolerance && point_line_distance(c2, p0, p1) <= tolerance
+}
+
+fn midpoint(a: Vec2, b: Vec2) -> Vec2 {
+	Vec2((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5)
+}
+
+// The best single quadratic Bezier approximating a cubic Bezier that shares
+// its endpoints, by the standard degree-reduction formula.
+fn quad_control_from_cubic(p0: Vec2, c1: Vec2, c2: Vec2, p1: Vec2) -> Vec2 {
+	Vec2(
+		(-p0.0 + 3.0 * c1.0 + 3.0 * c2.0 - p1.0) / 4.0,
+		(-p0.1 + 3.0 * c1.1 + 3.0 * c2.1 - p1.1) / 4.0,
+	)
+}
+
+// Recursively subdivide a cubic Bezier at its midpoint (De Casteljau) until
+// the control polygon's deviation from the chord is within `tolerance`,
+// emitting `(control, point)` pairs describing a chain of `QuadTo`s.
+fn flatten_cubic(p0: Vec2, c1: Vec2, c2: Vec2, p1: Vec2, tolerance: f32, depth: u32, out: &mut Vec<(Vec2, Vec2)>) {
+	if depth >= CUBIC_FLATTEN_MAX_DEPTH || cubic_is_flat(p0, c1, c2, p1, tolerance) {
+		out.push((quad_control_from_cubic(p0, c1, c2, p1), p1));
+		return;
+	}
+
+	let p01 = midpoint(p0, c1);
+	let p12 = midpoint(c1, c2);
+	let p23 = midpoint(c2, p1);
+	let p012 = midpoint(p01, p12);
+	let p123 = midpoint(p12, p23);
+	let p0123 = midpoint(p012, p123);
+
+	flatten_cubic(p0, p01, p012, p0123, tolerance, depth + 1, out);
+	flatten_cubic(p0123, p123, p23, p1, tolerance, depth + 1, out);
 }
 
 /// A collection of fonts read straight from a font file's data. The data in the
@@ -122,6 +270,13 @@ pub struct FontCollection<'a>(SharedBytes<'a>);
 #[derive(Clone)]
 pub struct Font<'a> {
 	info: tt::FontInfo<SharedBytes<'a>>,
+	// The same bytes `info` was built from, plus the offset of this font
+	// within them. `FontInfo` keeps its own private copy of both, but
+	// doesn't expose either, so metadata that it has no accessor for (the
+	// `OS/2` and `head` tables, used by `style`) is read straight out of
+	// this copy instead.
+	data: SharedBytes<'a>,
+	offset: u32,
 }
 
 /// `SharedBytes` handles the lifetime of font data used in Fonterator. The data
@@ -254,8 +409,22 @@ impl<'a> FontCollection<'a> {
 	///
 	/// This returns an error if `bytes` does not seem to be font data in a
 	/// format we recognize.
+	///
+	/// WOFF and WOFF2 data is also accepted: it is transparently
+	/// decompressed into an in-memory SFNT buffer first, so the rest of the
+	/// collection works exactly as if a raw TrueType/OpenType file had been
+	/// passed in.
 	pub fn new<B: Into<SharedBytes<'a>>>(bytes: B) -> Result<FontCollection<'a>, Error> {
 		let bytes = bytes.into();
+
+		if bytes.len() >= 4 {
+			match read_u32(&bytes, 0) {
+				WOFF_MAGIC => return Ok(FontCollection(decompress_woff(&bytes)?.into())),
+				WOFF2_MAGIC => return Ok(FontCollection(decompress_woff2(&bytes)?.into())),
+				_ => {}
+			}
+		}
+
 		// We should use tt::is_collection once it lands in stb_truetype-rs:
 		// https://github.com/redox-os/stb_truetype-rs/pull/15
 		if !tt::is_font(&bytes) && &bytes[0..4] != b"ttcf" {
@@ -290,8 +459,9 @@ impl<'a> FontCollection<'a> {
 				Some(offset) => offset,
 			}
 		};
+		let data = self.0.clone();
 		let info = tt::FontInfo::new(self.0, offset as usize).ok_or(Error::IllFormed)?;
-		Ok(Font { info })
+		Ok(Font { info, data, offset })
 	}
 	/// Gets the font at index `i` in the font collection, if it exists and is
 	/// valid. The produced font borrows the font data that is either borrowed
@@ -300,7 +470,7 @@ impl<'a> FontCollection<'a> {
 		let offset = tt::get_font_offset_for_index(&self.0, i as i32)
 			.ok_or(Error::CollectionIndexOutOfBounds)?;
 		let info = tt::FontInfo::new(self.0.clone(), offset as usize).ok_or(Error::IllFormed)?;
-		Ok(Font { info })
+		Ok(Font { info, data: self.0.clone(), offset })
 	}
 	/// Converts `self` into an `Iterator` yielding each `Font` that exists
 	/// within the collection.
@@ -319,48 +489,241 @@ impl<'a> FontCollection<'a> {
 
 		fonts
 	}
+	/// Scores every face in this collection against `family`, `weight`, and
+	/// `slant`, and returns the closest match, or `None` if the collection
+	/// is empty.
+	///
+	/// Matching a `family_name()` is worth far more than any weight/slant
+	/// difference, so a requested family is always preferred over a
+	/// same-weight face from a different family; within a family (or when
+	/// none matches), a face with matching `slant` is preferred over any
+	/// weight difference, and the face closest in weight among those wins.
+	/// This mirrors the fontconfig/CoreText-style
+	/// scoring that tools like Alacritty use to pick a face out of a
+	/// collection.
+	pub fn best_match(&self, family: &str, weight: u16, slant: Slant) -> Option<Font<'a>> {
+		let mut best: Option<(Font<'a>, i64)> = None;
+		let mut index = 0;
+
+		loop {
+			let result = self.font_at(index);
+			if let Err(Error::CollectionIndexOutOfBounds) = result {
+				break
+			}
+			index += 1;
+			// Skip faces this crate can't parse (e.g. CFF-only OpenType
+			// fonts) instead of panicking; every other face is still
+			// considered.
+			let font = match result {
+				Ok(font) => font,
+				Err(_) => continue,
+			};
+
+			let family_match = font
+				.family_name()
+				.map(|name| name.eq_ignore_ascii_case(family))
+				.unwrap_or(false);
+			let style = font.style();
+			let weight_diff = (style.weight as i64 - weight as i64).abs();
+			let slant_diff = if style.slant == slant { 0 } else { 1_000 };
+			let score = if family_match { 0 } else { 1_000_000 } + weight_diff + slant_diff;
+
+			// `Option::is_none_or` reads better here, but it only landed in
+			// Rust 1.82; `map_or` keeps this crate's MSRV where it already
+			// was.
+			#[allow(clippy::unnecessary_map_or)]
+			let replaces_best = best.as_ref().map_or(true, |&(_, best_score)| score < best_score);
+			if replaces_best {
+				best = Some((font, score));
+			}
+		}
+
+		best.map(|(font, _)| font)
+	}
+}
+
+/// An ordered list of fallback fonts, consulted in turn for each codepoint so
+/// that a string can be laid out and drawn even when no single font covers
+/// every character in it (mixed-script or emoji-containing text, say).
+///
+/// Use `FontCascade::glyphs` exactly as you would `Font::glyphs`; the
+/// returned `GlyphIterator` picks whichever font in the cascade actually has
+/// a glyph for each character, falling back to the primary font's `.notdef`
+/// glyph only if none of them do.
+#[derive(Clone)]
+pub struct FontCascade<'a>(Vec<Font<'a>>);
+
+impl<'a> FontCascade<'a> {
+	/// Builds a cascade from an ordered list of fonts. The first font is the
+	/// primary font: later fonts are only consulted for codepoints that the
+	/// primary, and every font before them in the list, has no glyph for.
+	///
+	/// # Panics
+	///
+	/// Panics if `fonts` is empty: a cascade with no primary font has no
+	/// sensible `.notdef` glyph to fall back to.
+	pub fn new(fonts: Vec<Font<'a>>) -> FontCascade<'a> {
+		assert!(!fonts.is_empty(), "FontCascade::new requires at least one font");
+		FontCascade(fonts)
+	}
+	/// The primary font of the cascade: the first one consulted, and the one
+	/// whose `.notdef` glyph is used if no font in the cascade has a glyph
+	/// for a codepoint.
+	pub fn primary(&self) -> &Font<'a> {
+		&self.0[0]
+	}
+	/// Get an iterator over the glyphs in a string, searching the cascade for
+	/// each character in turn. See the `FontCascade` documentation.
+	pub fn glyphs<T: ToString>(&'a self, text: T, scale: (f32, f32)) -> GlyphIterator<'a> {
+		self.glyphs_dir(text, scale, LayoutDir::LeftToRight)
+	}
+	/// Like `glyphs`, but laying the string out in the given `LayoutDir`
+	/// instead of assuming left-to-right.
+	pub fn glyphs_dir<T: ToString>(&'a self, text: T, scale: (f32, f32), dir: LayoutDir) -> GlyphIterator<'a> {
+		glyph_iterator(&self.0, text.to_string(), scale, dir)
+	}
+}
+
+/// Which direction a `GlyphIterator` lays a string out in, for
+/// `Font::glyphs_dir`/`FontCascade::glyphs_dir`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LayoutDir {
+	/// The pen advances rightward, one glyph after another. This is what
+	/// `glyphs` uses.
+	LeftToRight,
+	/// The pen advances leftward, one glyph after another, and kerning is
+	/// read in mirrored (right-to-left) order.
+	RightToLeft,
+	/// The pen advances downward, one glyph after another, using the font's
+	/// vertical metrics instead of its horizontal ones.
+	TopToBottom,
+}
+
+// Finds the first font in `fonts` with a glyph for `c`, falling back to
+// `fonts[0]`'s `.notdef` glyph if none of them have one.
+fn find_glyph_font<'a>(fonts: &'a [Font<'a>], c: char) -> (usize, &'a Font<'a>) {
+	for (i, font) in fonts.iter().enumerate() {
+		if font.info.find_glyph_index(c as u32) != 0 {
+			return (i, font);
+		}
+	}
+	(0, &fonts[0])
+}
+
+fn glyph_iterator<'a>(fonts: &'a [Font<'a>], string: String, scale: (f32, f32), dir: LayoutDir) -> GlyphIterator<'a> {
+	GlyphIterator {
+		fonts,
+		api_scale: scale,
+		dir,
+		source: GlyphSource::Codepoints(string.nfc().collect::<Vec<char>>()),
+		cursor: 0,
+		last: None,
+	}
+}
+
+// What a `GlyphIterator` walks over: either the raw codepoints of a string
+// (resolved to a glyph per-character via cascade fallback, as `glyphs` and
+// `glyphs_dir` do), or a sequence of glyph IDs that has already been
+// resolved some other way (as `shape` does, after GSUB substitution may
+// have merged several codepoints into one ligature glyph).
+enum GlyphSource {
+	Codepoints(Vec<char>),
+	Glyphs(Vec<GlyphId>),
+}
+
+impl GlyphSource {
+	fn len(&self) -> usize {
+		match self {
+			GlyphSource::Codepoints(v) => v.len(),
+			GlyphSource::Glyphs(v) => v.len(),
+		}
+	}
 }
 
 /// An iterator over glyphs in a string.
 pub struct GlyphIterator<'a> {
-	// The font
-	font: &'a Font<'a>,
-	// Scaling info
+	// The fonts to search for each glyph, in cascade order; `fonts[0]` is
+	// the primary font for a plain `Font::glyphs` iterator. `shape` always
+	// has exactly one font here, since GSUB lookups are font-specific.
+	fonts: &'a [Font<'a>],
+	// Scaling info, as passed in by the caller
 	api_scale: (f32, f32),
-	// ...
-	scale: Vec2,
-	// Normalized string
-	string: Vec<char>,
-	// Which character in the string
+	// Which way the pen moves from one glyph to the next.
+	dir: LayoutDir,
+	// The codepoints or pre-resolved glyph IDs to lay out
+	source: GlyphSource,
+	// Which element of `source` is next
 	cursor: usize,
-	// The previous glyph
-	last: Option<Glyph<'a>>,
+	// The previous glyph, and the index into `fonts` it was drawn from.
+	last: Option<(Glyph<'a>, usize)>,
 }
 
 impl<'a> Iterator for GlyphIterator<'a> {
-	type Item = (Glyph<'a>, f32);
+	// The glyph, and the pen movement (in the direction the iterator was
+	// built with) to apply before placing the next glyph.
+	type Item = (Glyph<'a>, Vec2);
 
-	fn next(&mut self) -> Option<(Glyph<'a>, f32)> {
-		let c = self.string.get(self.cursor);
+	fn next(&mut self) -> Option<(Glyph<'a>, Vec2)> {
+		if self.cursor >= self.source.len() {
+			return None;
+		}
 
-		if let Some(c) = c {
-			let glyph: Glyph<'a> = self.font.glyph(*c, self.scale);
-			let mut advance = self.font.info
+		let (font_idx, font, scale, glyph): (usize, &'a Font<'a>, Vec2, Glyph<'a>) =
+			match &self.source {
+				GlyphSource::Codepoints(chars) => {
+					let c = chars[self.cursor];
+					let (font_idx, font) = find_glyph_font(self.fonts, c);
+					let scale = font.pixel_scale(self.api_scale);
+					(font_idx, font, scale, font.glyph(c, scale))
+				}
+				GlyphSource::Glyphs(ids) => {
+					let font = &self.fonts[0];
+					let scale = font.pixel_scale(self.api_scale);
+					(0, font, scale, font.glyph(ids[self.cursor], scale))
+				}
+			};
+
+		let movement = if self.dir == LayoutDir::TopToBottom {
+			// Prefer this glyph's own `vhea`/`vmtx` advance height; fall
+			// back to the font's global line height for fonts that have
+			// no vertical metrics tables at all.
+			let advance = font
+				.glyph_v_advance(glyph.id().0)
+				.map(|units| units as f32 * scale.1)
+				.unwrap_or_else(|| {
+					let vm = font.info.get_v_metrics();
+					(vm.ascent - vm.descent + vm.line_gap) as f32 * scale.1
+				});
+			Vec2(0.0, advance)
+		} else {
+			let mut advance = font.info
 				.get_glyph_h_metrics(glyph.id().0)
-				.advance_width as f32 * self.scale.0;
+				.advance_width as f32 * scale.0;
 
-			if self.cursor != 0 {
-				advance += self.font.kerning(self.api_scale,
-					self.scale, self.last.as_ref().unwrap(),
-					&glyph);
+			// Kerning tables are per-font, so only apply kerning when
+			// the previous glyph came from the same font as this one.
+			if let Some((last, last_idx)) = &self.last {
+				if *last_idx == font_idx {
+					// Right-to-left, this glyph sits to the left of the
+					// previous one, so the kerning pair is read mirrored.
+					advance += if self.dir == LayoutDir::RightToLeft {
+						font.kerning(self.api_scale, scale, &glyph, last)
+					} else {
+						font.kerning(self.api_scale, scale, last, &glyph)
+					};
+				}
 			}
 
-			self.last = Some(glyph.clone());
-			self.cursor += 1;
-			Some((glyph, advance))
-		} else {
-			None
-		}
+			if self.dir == LayoutDir::RightToLeft {
+				Vec2(-advance, 0.0)
+			} else {
+				Vec2(advance, 0.0)
+			}
+		};
+
+		self.last = Some((glyph.clone(), font_idx));
+		self.cursor += 1;
+		Some((glyph, movement))
 	}
 }
 
@@ -410,6 +773,116 @@ impl<'a> Font<'a> {
 	pub fn font_name_strings(&self) -> tt::FontNameIter<SharedBytes<'a>> {
 		self.info.get_font_name_strings()
 	}
+	/// Returns this font's family name, read from the `name` table's
+	/// "Typographic Family" record (name ID 16) if present, falling back to
+	/// the plain "Family" record (name ID 1).
+	pub fn family_name(&self) -> Option<String> {
+		self.name_string(16).or_else(|| self.name_string(1))
+	}
+	/// Returns this font's PostScript name, read from the `name` table
+	/// (name ID 6).
+	pub fn post_script_name(&self) -> Option<String> {
+		self.name_string(6)
+	}
+	// Picks the best available `name` table record for `name_id` and
+	// decodes it to a `String`, preferring a Unicode/Windows record (stored
+	// as UTF-16BE) over a Macintosh one (stored as near-ASCII Mac Roman,
+	// which is decoded byte-for-byte here since family/PostScript names are
+	// overwhelmingly plain Latin text).
+	fn name_string(&self, name_id: u16) -> Option<String> {
+		let mut best: Option<(&[u8], bool)> = None;
+		for (raw, platform, id) in self.info.get_font_name_strings() {
+			if id != name_id || raw.is_empty() {
+				continue;
+			}
+			let is_unicode = matches!(
+				platform,
+				Some(tt::PlatformEncodingLanguageId::Unicode(..))
+					| Some(tt::PlatformEncodingLanguageId::Microsoft(..))
+			);
+			if best.is_none() || is_unicode {
+				best = Some((raw, is_unicode));
+			}
+			if is_unicode {
+				break;
+			}
+		}
+		best.map(|(raw, is_unicode)| {
+			if is_unicode {
+				let units: Vec<u16> = raw
+					.chunks_exact(2)
+					.map(|c| u16::from_be_bytes([c[0], c[1]]))
+					.collect();
+				String::from_utf16_lossy(&units)
+			} else {
+				raw.iter().map(|&b| b as char).collect()
+			}
+		})
+	}
+	/// Returns the weight and slant read from this font's `OS/2` table, or
+	/// from `head.macStyle` for the (now rare) fonts that lack an `OS/2`
+	/// table.
+	pub fn style(&self) -> Style {
+		if let Some(os2) = self.find_table(b"OS/2") {
+			let os2 = os2 as usize;
+			if os2 + 64 <= self.data.len() {
+				let weight = read_u16(&self.data, os2 + 4);
+				let fs_selection = read_u16(&self.data, os2 + 62);
+				let slant = if fs_selection & 0x01 != 0 {
+					Slant::Italic
+				} else {
+					Slant::Upright
+				};
+				return Style { weight, slant };
+			}
+		}
+
+		let mac_style = self
+			.find_table(b"head")
+			.filter(|&head| head as usize + 46 <= self.data.len())
+			.map(|head| read_u16(&self.data, head as usize + 44))
+			.unwrap_or(0);
+		Style {
+			weight: if mac_style & 0x01 != 0 { 700 } else { 400 },
+			slant: if mac_style & 0x02 != 0 {
+				Slant::Italic
+			} else {
+				Slant::Upright
+			},
+		}
+	}
+	/// Shorthand for `self.style().weight >= 700`.
+	pub fn is_bold(&self) -> bool {
+		self.style().weight >= 700
+	}
+	/// Shorthand for `self.style().slant == Slant::Italic`.
+	pub fn is_italic(&self) -> bool {
+		self.style().slant == Slant::Italic
+	}
+	// Scans this font's own SFNT table directory (not the whole
+	// collection's) for `tag`, returning its offset from the start of the
+	// file data. `stb_truetype` parses several tables internally but never
+	// surfaces their offsets, so metadata it doesn't expose (`OS/2`) is
+	// looked up the same way it looks up tables itself.
+	fn find_table(&self, tag: &[u8; 4]) -> Option<u32> {
+		find_table_offset(&self.data, self.offset as usize, tag)
+	}
+	// This glyph's vertical advance, in font units, read straight out of the
+	// `vhea`/`vmtx` tables (`stb_truetype` only exposes horizontal metrics).
+	// `vmtx`'s long metrics array covers the first `numOfLongVerMetrics`
+	// glyphs; every glyph after that reuses the last entry's advance height,
+	// per the `vmtx` table spec. Returns `None` for fonts that have no
+	// vertical metrics tables at all.
+	fn glyph_v_advance(&self, glyph_id: u32) -> Option<u16> {
+		let vhea = self.find_table(b"vhea")? as usize;
+		let num_long_ver_metrics = try_read_u16(&self.data, vhea + 34)? as usize;
+		if num_long_ver_metrics == 0 {
+			return None;
+		}
+		let vmtx = self.find_table(b"vmtx")? as usize;
+		let index = (glyph_id as usize).min(num_long_ver_metrics - 1);
+		try_read_u16(&self.data, vmtx + 4 * index)
+	}
 	/// Returns additional kerning to apply as well as that given by HMetrics
 	/// for a particular pair of glyphs.
 	fn pair_kerning<A, B>(&self, scale: (f32, f32), v: Vec2, first: A, second: B) -> f32
@@ -423,21 +896,57 @@ impl<'a> Font<'a> {
 			.get_glyph_kern_advance(first.id().0, second.id().0);
 		factor * kern as f32
 	}
+	// Converts the caller-facing `(horizontal, vertical)` pixel scale into
+	// the font-unit scale factors `stb_truetype` expects.
+	fn pixel_scale(&self, api_scale: (f32, f32)) -> Vec2 {
+		let scale_y = self.info.scale_for_pixel_height(api_scale.1);
+		let scale_x = scale_y * api_scale.0 / api_scale.1;
+		Vec2(scale_x, scale_y)
+	}
 	/// Get an iterator over the glyphs in a string.
 	pub fn glyphs<T: ToString>(&'a self, text: T, scale: (f32, f32))
 		-> GlyphIterator<'a>
 	{
-		let (scale_x, scale_y) = {
-			let scale_y = self.info.scale_for_pixel_height(scale.1);
-			let scale_x = scale_y * scale.0 / scale.1;
-			(scale_x, scale_y)
-		};
+		self.glyphs_dir(text, scale, LayoutDir::LeftToRight)
+	}
+	/// Like `glyphs`, but laying the string out in the given `LayoutDir`
+	/// (right-to-left or top-to-bottom) instead of assuming left-to-right.
+	pub fn glyphs_dir<T: ToString>(&'a self, text: T, scale: (f32, f32), dir: LayoutDir)
+		-> GlyphIterator<'a>
+	{
+		glyph_iterator(std::slice::from_ref(self), text.to_string(), scale, dir)
+	}
+	/// Like `glyphs`, but first runs the (NFC-normalized) text through this
+	/// font's `GSUB` table, applying single (lookup type 1) and ligature
+	/// (lookup type 4) substitutions for the default script/language's
+	/// default-on features (`GSUB_DEFAULT_FEATURES`) before laying anything
+	/// out. This lets sequences like "fi" come out as their ligature glyph
+	/// instead of two separate glyphs, at the cost of an extra pass over the
+	/// codepoint stream, so `glyphs` remains the fast default for callers
+	/// who don't need it.
+	///
+	/// Optional features (small caps, stylistic sets, swashes, and the
+	/// like) are never applied, since a caller who wants those has to ask
+	/// for them some other way; this crate has no API for that yet.
+	///
+	/// Fonts with no `GSUB` table, or whose `GSUB` table this parser can't
+	/// make sense of, shape exactly like `glyphs`.
+	pub fn shape<T: ToString>(&'a self, text: T, scale: (f32, f32)) -> GlyphIterator<'a> {
+		let glyphs: Vec<u16> = text
+			.to_string()
+			.nfc()
+			.map(|c| self.info.find_glyph_index(c as u32) as u16)
+			.collect();
+		let shaped = gsub_shape(self, &glyphs)
+			.into_iter()
+			.map(|id| GlyphId(id as u32))
+			.collect();
 
 		GlyphIterator {
-			font: &self,
+			fonts: std::slice::from_ref(self),
 			api_scale: scale,
-			scale: Vec2(scale_x, scale_y),
-			string: text.to_string().nfc().collect::<Vec<char>>(),
+			dir: LayoutDir::LeftToRight,
+			source: GlyphSource::Glyphs(shaped),
 			cursor: 0,
 			last: None,
 		}
@@ -449,6 +958,26 @@ impl<'a> Font<'a> {
 		self.pair_kerning(scale, v, first.id(), second.id())
 	}
 }
+
+/// A font's nominal weight and slant, as read by `Font::style` from its
+/// `OS/2`/`head` tables. Used by `FontCollection::best_match` to score how
+/// closely a face matches a request.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Style {
+	/// The font's weight class, on the usual 100 (thin) to 900 (black)
+	/// scale; 400 is regular weight and 700 is bold.
+	pub weight: u16,
+	/// Whether the font is upright or slanted.
+	pub slant: Slant,
+}
+
+/// Whether a font is upright or slanted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Slant {
+	Upright,
+	Italic,
+}
+
 impl<'a> Glyph<'a> {
 	fn new(inner: GlyphInner<'a>, v: Vec2) -> Glyph<'a> {
 		Glyph { inner, v }
@@ -461,7 +990,14 @@ impl<'a> Glyph<'a> {
 	fn id(&self) -> GlyphId {
 		GlyphId(self.inner.1)
 	}
-	/// Convert the glyph to an iterator over PathOps
+	/// Convert the glyph to an iterator over PathOps.
+	///
+	/// `stb_truetype`'s glyph outlines are always quadratic (the `glyf`
+	/// table never stores cubics), so this never emits `PathOp::CurveTo` /
+	/// `PathOp::CurveClose` today; those variants exist so a future
+	/// CFF-flavored outline source can produce cubic segments without
+	/// changing the `Path` surface, and `Path::into_quadratic` is always
+	/// available to flatten them for consumers that only handle quadratics.
 	pub fn draw(&self, point_x: f32, mut point_y: f32) -> Path {
 		use stb_truetype::VertexType;
 		point_y += self.font().v_metrics(self.v);
@@ -507,6 +1043,749 @@ impl<'a> Glyph<'a> {
 
 		Path(path)
 	}
+	/// Rasterizes this glyph into a tightly-cropped 8-bit alpha coverage
+	/// bitmap, using the signed-area algorithm used by font-rs and fontdue.
+	/// Returns the bounds of the produced bitmap along with the coverage
+	/// buffer itself, stored row-major starting from the top-left corner.
+	pub fn rasterize(&self) -> (OutlineBounds, Vec<u8>) {
+		// `into_quadratic` is a no-op pass-through for paths that are
+		// already all-quadratic (the common case today), and flattens any
+		// cubic segments otherwise, so `flatten_to_lines` only ever has to
+		// deal with `MoveTo`/`LineTo`/`QuadTo`/close ops.
+		let path = self.draw(0.0, 0.0).into_quadratic(RASTER_CUBIC_TOLERANCE);
+		let lines = flatten_to_lines(path);
+
+		if lines.is_empty() {
+			let bounds = OutlineBounds { xmin: 0, ymin: 0, width: 0, height: 0 };
+			return (bounds, Vec::new());
+		}
+
+		let (mut xmin, mut ymin) = (f32::MAX, f32::MAX);
+		let (mut xmax, mut ymax) = (f32::MIN, f32::MIN);
+		for &(Vec2(x0, y0), Vec2(x1, y1)) in &lines {
+			xmin = xmin.min(x0).min(x1);
+			ymin = ymin.min(y0).min(y1);
+			xmax = xmax.max(x0).max(x1);
+			ymax = ymax.max(y0).max(y1);
+		}
+
+		// Round outward so the outline always fits inside the bitmap.
+		let xmin_i = xmin.floor() as i32;
+		let ymin_i = ymin.floor() as i32;
+		let width = (xmax.ceil() as i32 - xmin_i).max(0) as usize;
+		let height = (ymax.ceil() as i32 - ymin_i).max(0) as usize;
+
+		let bounds = OutlineBounds { xmin: xmin_i, ymin: ymin_i, width, height };
+		if width == 0 || height == 0 {
+			return (bounds, Vec::new());
+		}
+
+		// One extra column per row absorbs coverage that spills past the
+		// right edge of the bitmap so it never indexes out of bounds.
+		let mut accum = vec![0.0f32; (width + 1) * height];
+		for (p0, p1) in lines {
+			let p0 = Vec2(p0.0 - xmin_i as f32, p0.1 - ymin_i as f32);
+			let p1 = Vec2(p1.0 - xmin_i as f32, p1.1 - ymin_i as f32);
+			accumulate_line(&mut accum, width, height, p0, p1);
+		}
+
+		let mut bitmap = vec![0u8; width * height];
+		for row in 0..height {
+			let mut sum = 0.0f32;
+			let accum_row = &accum[row * (width + 1)..row * (width + 1) + width];
+			let bitmap_row = &mut bitmap[row * width..(row + 1) * width];
+			for (a, b) in accum_row.iter().zip(bitmap_row.iter_mut()) {
+				sum += *a;
+				*b = (sum.abs().min(1.0) * 255.0) as u8;
+			}
+		}
+
+		(bounds, bitmap)
+	}
+}
+
+/// The pixel-space bounds of a rasterized glyph outline, as returned by
+/// `Glyph::rasterize`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OutlineBounds {
+	/// The x coordinate of the left edge of the bitmap.
+	pub xmin: i32,
+	/// The y coordinate of the top edge of the bitmap.
+	pub ymin: i32,
+	/// The width of the bitmap, in pixels.
+	pub width: usize,
+	/// The height of the bitmap, in pixels.
+	pub height: usize,
+}
+
+// How many line segments a quadratic curve is flattened into before being
+// handed to the rasterizer.
+const RASTER_QUAD_SEGMENTS: usize = 8;
+
+// Tolerance, in pixels, used to flatten any cubic segments in a glyph's
+// path before rasterizing it.
+const RASTER_CUBIC_TOLERANCE: f32 = 0.1;
+
+// Flatten a `Path` into a list of line segments in the same coordinate space,
+// following `MoveTo`/`LineTo`/`QuadTo` and their `Close` variants.
+fn flatten_to_lines(path: Path) -> Vec<(Vec2, Vec2)> {
+	let mut lines = Vec::new();
+	let mut cur = Vec2(0.0, 0.0);
+	let mut start = Vec2(0.0, 0.0);
+
+	for op in path {
+		match op {
+			PathOp::MoveTo(x, y) => {
+				cur = Vec2(x, y);
+				start = cur;
+			}
+			PathOp::LineTo(x, y) => {
+				let next = Vec2(x, y);
+				lines.push((cur, next));
+				cur = next;
+			}
+			PathOp::QuadTo(x, y, cx, cy) => {
+				let next = Vec2(x, y);
+				flatten_quad_into(cur, Vec2(cx, cy), next, &mut lines);
+				cur = next;
+			}
+			PathOp::LineClose => {
+				lines.push((cur, start));
+				cur = start;
+			}
+			PathOp::QuadClose(cx, cy) => {
+				flatten_quad_into(cur, Vec2(cx, cy), start, &mut lines);
+				cur = start;
+			}
+			PathOp::CurveTo(..) | PathOp::CurveClose(..) => {
+				// Callers are expected to flatten cubics with
+				// `Path::into_quadratic` before handing a path to the
+				// rasterizer; see `Glyph::rasterize`.
+				unreachable!("cubic PathOp reached flatten_to_lines; call Path::into_quadratic first")
+			}
+		}
+	}
+
+	lines
+}
+
+fn flatten_quad_into(p0: Vec2, c: Vec2, p1: Vec2, lines: &mut Vec<(Vec2, Vec2)>) {
+	let mut prev = p0;
+	for i in 1..=RASTER_QUAD_SEGMENTS {
+		let t = i as f32 / RASTER_QUAD_SEGMENTS as f32;
+		let mt = 1.0 - t;
+		let x = mt * mt * p0.0 + 2.0 * mt * t * c.0 + t * t * p1.0;
+		let y = mt * mt * p0.1 + 2.0 * mt * t * c.1 + t * t * p1.1;
+		let next = Vec2(x, y);
+		lines.push((prev, next));
+		prev = next;
+	}
+}
+
+// Accumulate the signed trapezoidal coverage of a single line segment into
+// `accum`, a `width + 1` by `height` buffer of per-cell coverage deltas. This
+// is the font-rs/fontdue "signed area" algorithm: each row the segment spans
+// gets the exact area swept between the segment and the left edge of the
+// bitmap, signed by the direction of travel in `y`, distributed across the
+// cells the segment crosses within that row. Integrating a running sum along
+// each row later turns these deltas into total coverage.
+fn accumulate_line(accum: &mut [f32], width: usize, height: usize, p0: Vec2, p1: Vec2) {
+	if p0.1 == p1.1 {
+		// Horizontal segments contribute no area.
+		return;
+	}
+
+	let (dir, p0, p1) = if p0.1 < p1.1 {
+		(1.0f32, p0, p1)
+	} else {
+		(-1.0f32, p1, p0)
+	};
+
+	let dxdy = (p1.0 - p0.0) / (p1.1 - p0.1);
+	let mut x = p0.0;
+
+	let y0 = p0.1.max(0.0);
+	let y1 = p1.1.min(height as f32);
+	if y0 >= y1 {
+		return;
+	}
+	if p0.1 < 0.0 {
+		x += dxdy * (0.0 - p0.1);
+	}
+
+	let mut y = y0;
+	let row_start = y0 as usize;
+	let row_end = y1.ceil() as usize;
+
+	for row in row_start..row_end.min(height) {
+		let dy = (row as f32 + 1.0).min(y1) - y.max(row as f32).max(y0);
+		if dy <= 0.0 {
+			continue;
+		}
+		let xnext = x + dxdy * dy;
+		let d = dy * dir;
+
+		let (x0, x1) = if x < xnext { (x, xnext) } else { (xnext, x) };
+		// Clamp to the row's bounds; any coverage that would fall to the
+		// left is folded into column 0, and anything spilling past the
+		// right edge carries into the padding column at index `width`.
+		let x0 = x0.max(0.0).min(width as f32);
+		let x1 = x1.max(0.0).min(width as f32);
+
+		let row_off = row * (width + 1);
+		let x0floor = x0.floor();
+		let x0i = x0floor as usize;
+		let x1ceil = x1.ceil();
+		let x1i = (x1ceil as usize).min(width);
+
+		if x1i <= x0i + 1 {
+			let xmf = 0.5 * (x0 + x1) - x0floor;
+			accum[row_off + x0i] += d - d * xmf;
+			if x0i < width {
+				accum[row_off + x0i + 1] += d * xmf;
+			}
+		} else {
+			let s = (x1 - x0).recip();
+			let x0f = x0 - x0floor;
+			let a0 = 0.5 * s * (1.0 - x0f) * (1.0 - x0f);
+			let x1f = x1 - x1ceil + 1.0;
+			let am = 0.5 * s * x1f * x1f;
+
+			accum[row_off + x0i] += d * a0;
+			if x1i == x0i + 2 {
+				accum[row_off + x0i + 1] += d * (1.0 - a0 - am);
+			} else {
+				let a1 = s * (1.5 - x0f);
+				accum[row_off + x0i + 1] += d * (a1 - a0);
+				for xi in (x0i + 2)..(x1i - 1) {
+					accum[row_off + xi] += d * s;
+				}
+				let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+				accum[row_off + x1i - 1] += d * (1.0 - a2 - am);
+			}
+			accum[row_off + x1i] += d * am;
+		}
+
+		x = xnext;
+		y = (row as f32 + 1.0).min(y1);
+	}
+}
+
+const WOFF_MAGIC: u32 = 0x774F_4646; // "wOFF"
+const WOFF2_MAGIC: u32 = 0x774F_4632; // "wOF2"
+
+fn read_u16(b: &[u8], at: usize) -> u16 {
+	u16::from_be_bytes([b[at], b[at + 1]])
+}
+
+fn read_u32(b: &[u8], at: usize) -> u32 {
+	u32::from_be_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]])
+}
+
+// Scans a single SFNT's table directory (starting at `fontstart`, as
+// returned by `tt::get_font_offset_for_index`) for a table tagged `tag`,
+// returning its offset from the start of `data`. `stb_truetype` has its own
+// private version of this for the tables it knows about; this is for the
+// ones it doesn't expose an accessor for, like `OS/2`.
+fn find_table_offset(data: &[u8], fontstart: usize, tag: &[u8; 4]) -> Option<u32> {
+	if fontstart + 12 > data.len() {
+		return None;
+	}
+	let num_tables = read_u16(data, fontstart + 4) as usize;
+	for i in 0..num_tables {
+		let entry = fontstart + 12 + 16 * i;
+		if entry + 16 > data.len() {
+			return None;
+		}
+		if &data[entry..entry + 4] == tag {
+			return Some(read_u32(data, entry + 8));
+		}
+	}
+	None
+}
+
+// A bounds-checked counterpart to `read_u16`, for table formats (like
+// `GSUB`) this crate doesn't otherwise validate the length of up front.
+fn try_read_u16(data: &[u8], at: usize) -> Option<u16> {
+	data.get(at..at + 2)
+		.map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+// Scans an OpenType Coverage table (format 1: sorted glyph list; format 2:
+// sorted glyph ranges) for `glyph`, returning its coverage index if found.
+fn gsub_coverage_index(data: &[u8], cov_off: usize, glyph: u16) -> Option<u16> {
+	match try_read_u16(data, cov_off)? {
+		1 => {
+			let count = try_read_u16(data, cov_off + 2)? as usize;
+			(0..count).find_map(|i| {
+				let at = cov_off + 4 + 2 * i;
+				(try_read_u16(data, at)? == glyph).then_some(i as u16)
+			})
+		}
+		2 => {
+			let count = try_read_u16(data, cov_off + 2)? as usize;
+			(0..count).find_map(|i| {
+				let at = cov_off + 4 + 6 * i;
+				let start = try_read_u16(data, at)?;
+				let end = try_read_u16(data, at + 2)?;
+				let start_index = try_read_u16(data, at + 4)?;
+				// Not `(glyph >= start && glyph <= end).then_some(start_index +
+				// (glyph - start))`: `then_some`'s argument is eager, so
+				// `glyph - start` would underflow before the range check ever
+				// ran for any earlier, out-of-range, higher-numbered range.
+				if glyph >= start && glyph <= end {
+					Some(start_index + (glyph - start))
+				} else {
+					None
+				}
+			})
+		}
+		_ => None,
+	}
+}
+
+// Applies a GSUB lookup type 1 (Single Substitution) subtable to `glyph`,
+// returning its replacement if `glyph` is covered. Replacements that aren't
+// actually valid glyph ids in this font (`>= glyph_count`) are treated as if
+// the glyph weren't covered at all, same as any other GSUB malformation:
+// nothing stops a syntactically well-formed table from naming a glyph id
+// that doesn't exist, and callers downstream (`Font::glyph`) assert on that.
+fn gsub_single_subst(data: &[u8], sub_off: usize, glyph: u16, glyph_count: usize) -> Option<u16> {
+	let cov_off = sub_off + try_read_u16(data, sub_off + 2)? as usize;
+	let index = gsub_coverage_index(data, cov_off, glyph)?;
+	let replacement = match try_read_u16(data, sub_off)? {
+		1 => {
+			let delta = try_read_u16(data, sub_off + 4)? as i16;
+			(glyph as i32 + delta as i32) as u16
+		}
+		2 => try_read_u16(data, sub_off + 6 + 2 * index as usize)?,
+		_ => return None,
+	};
+	if replacement as usize >= glyph_count {
+		return None;
+	}
+	Some(replacement)
+}
+
+// Tries to match a GSUB lookup type 4 (Ligature Substitution) subtable
+// against the start of `glyphs`, returning the replacement glyph and how
+// many leading elements of `glyphs` it consumes, if any of the ligatures
+// covering `glyphs[0]` match. As with `gsub_single_subst`, a ligature glyph
+// that isn't actually `< glyph_count` is treated as not matching.
+fn gsub_ligature_subst(
+	data: &[u8],
+	sub_off: usize,
+	glyphs: &[u16],
+	glyph_count: usize,
+) -> Option<(u16, usize)> {
+	let cov_off = sub_off + try_read_u16(data, sub_off + 2)? as usize;
+	let index = gsub_coverage_index(data, cov_off, *glyphs.first()?)? as usize;
+	let ligset_off = sub_off + try_read_u16(data, sub_off + 6 + 2 * index)? as usize;
+	let lig_count = try_read_u16(data, ligset_off)? as usize;
+
+	'ligatures: for i in 0..lig_count {
+		let lig_off = ligset_off + try_read_u16(data, ligset_off + 2 + 2 * i)? as usize;
+		let lig_glyph = try_read_u16(data, lig_off)?;
+		let comp_count = try_read_u16(data, lig_off + 2)? as usize;
+		if comp_count == 0 || comp_count > glyphs.len() || lig_glyph as usize >= glyph_count {
+			continue;
+		}
+		for (c, &input_glyph) in glyphs.iter().enumerate().take(comp_count).skip(1) {
+			if try_read_u16(data, lig_off + 4 + 2 * (c - 1))? != input_glyph {
+				continue 'ligatures;
+			}
+		}
+		return Some((lig_glyph, comp_count));
+	}
+	None
+}
+
+// `GSUB` feature tags this crate applies on its own, without the caller
+// opting in. A `LangSys`'s feature list includes plenty of optional
+// features too (small caps, stylistic sets, swashes, ...), so shaping has
+// to pick and choose rather than running every feature it's handed; this
+// mirrors the default-on substitution feature set real shaping engines use.
+const GSUB_DEFAULT_FEATURES: [&[u8; 4]; 6] =
+	[b"ccmp", b"locl", b"rlig", b"calt", b"liga", b"clig"];
+
+// Picks the `GSUB` script this crate shapes with: the `DFLT` script if the
+// table has one, otherwise its first script, matching how most shaping
+// engines fall back when no caller-specified script applies.
+fn gsub_find_script(data: &[u8], script_list_off: usize) -> Option<usize> {
+	let count = try_read_u16(data, script_list_off)? as usize;
+	let mut first = None;
+	for i in 0..count {
+		let rec = script_list_off + 2 + 6 * i;
+		let off = script_list_off + try_read_u16(data, rec + 4)? as usize;
+		if first.is_none() {
+			first = Some(off);
+		}
+		if data.get(rec..rec + 4)? == b"DFLT" {
+			return Some(off);
+		}
+	}
+	first
+}
+
+// Returns the offset of a script's default `LangSys` table: the one its
+// `DefaultLangSys` offset points to, or its first explicit `LangSysRecord`
+// if it has no default.
+fn gsub_default_langsys(data: &[u8], script_off: usize) -> Option<usize> {
+	let default_off = try_read_u16(data, script_off)?;
+	if default_off != 0 {
+		return Some(script_off + default_off as usize);
+	}
+	let count = try_read_u16(data, script_off + 2)?;
+	if count == 0 {
+		return None;
+	}
+	let off = try_read_u16(data, script_off + 4 + 4)?;
+	Some(script_off + off as usize)
+}
+
+// Gathers the lookups referenced by a `LangSys`'s default-on features (see
+// `GSUB_DEFAULT_FEATURES`), deduplicated and sorted in ascending lookup-index
+// order as the `LookupList` ordering rule requires: lookups must run in
+// increasing index order regardless of which feature referenced them, since
+// a later lookup can depend on an earlier one having already run.
+fn gsub_lookup_indices(data: &[u8], feature_list_off: usize, langsys_off: usize) -> Option<Vec<u16>> {
+	let required_feature = try_read_u16(data, langsys_off + 2)?;
+	let feature_count = try_read_u16(data, langsys_off + 4)? as usize;
+	let mut feature_indices: Vec<usize> = if required_feature != 0xFFFF {
+		vec![required_feature as usize]
+	} else {
+		vec![]
+	};
+	for i in 0..feature_count {
+		feature_indices.push(try_read_u16(data, langsys_off + 6 + 2 * i)? as usize);
+	}
+
+	let mut lookup_indices = Vec::new();
+	for feature_index in feature_indices {
+		let rec = feature_list_off + 2 + 6 * feature_index;
+		let tag = data.get(rec..rec + 4)?;
+		if !GSUB_DEFAULT_FEATURES.iter().any(|default_tag| &default_tag[..] == tag) {
+			// A `LangSys`'s feature list includes optional features too
+			// (small caps, stylistic sets, swashes, ...); only apply the
+			// ones shaping engines turn on without the caller having to
+			// ask, same as `shape` does for everything else.
+			continue;
+		}
+		let feature_off = feature_list_off + try_read_u16(data, rec + 4)? as usize;
+		let lookup_count = try_read_u16(data, feature_off + 2)? as usize;
+		for j in 0..lookup_count {
+			let idx = try_read_u16(data, feature_off + 4 + 2 * j)?;
+			if !lookup_indices.contains(&idx) {
+				lookup_indices.push(idx);
+			}
+		}
+	}
+	lookup_indices.sort_unstable();
+
+	Some(lookup_indices)
+}
+
+// The actual work behind `Font::shape`: find the `GSUB` table's default
+// langsys for this font, gather every lookup its default-on features
+// reference, and apply the single/ligature substitution ones in turn.
+// Returns `None` as soon as anything about the table looks malformed, which
+// `gsub_shape` treats the same as there being no `GSUB` table at all.
+fn gsub_shape_inner(font: &Font, glyphs: &[u16]) -> Option<Vec<u16>> {
+	let gsub = font.find_table(b"GSUB")? as usize;
+	let data: &[u8] = &font.data;
+
+	let script_list_off = gsub + try_read_u16(data, gsub + 4)? as usize;
+	let feature_list_off = gsub + try_read_u16(data, gsub + 6)? as usize;
+	let lookup_list_off = gsub + try_read_u16(data, gsub + 8)? as usize;
+
+	let script_off = gsub_find_script(data, script_list_off)?;
+	let langsys_off = gsub_default_langsys(data, script_off)?;
+	let lookup_indices = gsub_lookup_indices(data, feature_list_off, langsys_off)?;
+	let glyph_count = font.glyph_count();
+
+	let mut glyphs = glyphs.to_vec();
+	for lookup_index in lookup_indices {
+		let lookup_off =
+			lookup_list_off + try_read_u16(data, lookup_list_off + 2 + 2 * lookup_index as usize)? as usize;
+		let lookup_type = try_read_u16(data, lookup_off)?;
+		if lookup_type != 1 && lookup_type != 4 {
+			// Only single and ligature substitutions are implemented;
+			// everything else (contextual, chaining, etc.) passes through
+			// unchanged.
+			continue;
+		}
+		let sub_count = try_read_u16(data, lookup_off + 4)? as usize;
+		let sub_offs: Vec<usize> = (0..sub_count)
+			.filter_map(|i| try_read_u16(data, lookup_off + 6 + 2 * i).map(|rel| lookup_off + rel as usize))
+			.collect();
+
+		glyphs = if lookup_type == 1 {
+			glyphs
+				.iter()
+				.map(|&g| {
+					sub_offs
+						.iter()
+						.find_map(|&so| gsub_single_subst(data, so, g, glyph_count))
+						.unwrap_or(g)
+				})
+				.collect()
+		} else {
+			let mut out = Vec::with_capacity(glyphs.len());
+			let mut i = 0;
+			while i < glyphs.len() {
+				match sub_offs
+					.iter()
+					.find_map(|&so| gsub_ligature_subst(data, so, &glyphs[i..], glyph_count))
+				{
+					Some((lig, consumed)) => {
+						out.push(lig);
+						i += consumed;
+					}
+					None => {
+						out.push(glyphs[i]);
+						i += 1;
+					}
+				}
+			}
+			out
+		};
+	}
+
+	Some(glyphs)
+}
+
+// `Font::shape`'s entry point: shape `glyphs` via `GSUB`, falling back to
+// them unchanged if the font has no usable `GSUB` table.
+fn gsub_shape(font: &Font, glyphs: &[u16]) -> Vec<u16> {
+	gsub_shape_inner(font, glyphs).unwrap_or_else(|| glyphs.to_vec())
+}
+
+// Reads a WOFF2 UIntBase128: a base-128 varint, most significant byte
+// first, at most 5 bytes, with no leading zero bytes.
+fn read_uint_base128(b: &[u8], pos: &mut usize) -> Result<u32, Error> {
+	let mut accum: u32 = 0;
+	for i in 0..5 {
+		let byte = *b.get(*pos).ok_or(Error::IllFormed)?;
+		*pos += 1;
+		if i == 0 && byte == 0x80 {
+			// No leading zero bytes allowed.
+			return Err(Error::IllFormed);
+		}
+		if accum & 0xFE00_0000 != 0 {
+			// Would overflow a u32 on the next shift.
+			return Err(Error::IllFormed);
+		}
+		accum = (accum << 7) | (byte & 0x7f) as u32;
+		if byte & 0x80 == 0 {
+			return Ok(accum);
+		}
+	}
+	Err(Error::IllFormed)
+}
+
+// Builds a plain SFNT byte buffer (table directory plus table data) out of a
+// `sfnt version`/flavor tag and a list of decompressed tables, as used to
+// reassemble WOFF and WOFF2 containers into something `stb_truetype` can
+// read unchanged.
+fn build_sfnt(flavor: u32, tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+	let num_tables = tables.len() as u16;
+	let mut entry_selector = 0u16;
+	while (1u16 << (entry_selector + 1)) <= num_tables {
+		entry_selector += 1;
+	}
+	let search_range = (1u16 << entry_selector).wrapping_mul(16);
+	let range_shift = num_tables.wrapping_mul(16).wrapping_sub(search_range);
+
+	let mut sorted: Vec<&([u8; 4], Vec<u8>)> = tables.iter().collect();
+	sorted.sort_by_key(|(tag, _)| *tag);
+
+	let mut out = Vec::new();
+	out.extend_from_slice(&flavor.to_be_bytes());
+	out.extend_from_slice(&num_tables.to_be_bytes());
+	out.extend_from_slice(&search_range.to_be_bytes());
+	out.extend_from_slice(&entry_selector.to_be_bytes());
+	out.extend_from_slice(&range_shift.to_be_bytes());
+
+	let directory_end = out.len() + 16 * sorted.len();
+	let mut directory = Vec::new();
+	let mut data = Vec::new();
+
+	for (tag, bytes) in &sorted {
+		let offset = directory_end + data.len();
+		directory.extend_from_slice(&tag[..]);
+		directory.extend_from_slice(&0u32.to_be_bytes()); // checksum, unused by stb_truetype
+		directory.extend_from_slice(&(offset as u32).to_be_bytes());
+		directory.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+
+		data.extend_from_slice(bytes);
+		while data.len() % 4 != 0 {
+			data.push(0);
+		}
+	}
+
+	out.extend_from_slice(&directory);
+	out.extend_from_slice(&data);
+	out
+}
+
+#[cfg(feature = "woff")]
+fn inflate_zlib(data: &[u8]) -> Result<Vec<u8>, Error> {
+	miniz_oxide::inflate::decompress_to_vec_zlib(data).map_err(|_| Error::IllFormed)
+}
+
+// Reassembles a WOFF 1.0 container into a plain SFNT byte buffer.
+fn decompress_woff(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+	#[cfg(not(feature = "woff"))]
+	{
+		let _ = bytes;
+		Err(Error::UnsupportedCompression)
+	}
+
+	#[cfg(feature = "woff")]
+	{
+		if bytes.len() < 44 {
+			return Err(Error::IllFormed);
+		}
+		let flavor = read_u32(bytes, 4);
+		let num_tables = read_u16(bytes, 12) as usize;
+
+		let mut tables = Vec::with_capacity(num_tables);
+		let mut pos = 44;
+		for _ in 0..num_tables {
+			if pos + 20 > bytes.len() {
+				return Err(Error::IllFormed);
+			}
+			let mut tag = [0u8; 4];
+			tag.copy_from_slice(&bytes[pos..pos + 4]);
+			let offset = read_u32(bytes, pos + 4) as usize;
+			let comp_length = read_u32(bytes, pos + 8) as usize;
+			let orig_length = read_u32(bytes, pos + 12) as usize;
+			pos += 20;
+
+			let end = offset.checked_add(comp_length).ok_or(Error::IllFormed)?;
+			let raw = bytes.get(offset..end).ok_or(Error::IllFormed)?;
+			let data = if comp_length == orig_length {
+				raw.to_vec()
+			} else {
+				inflate_zlib(raw)?
+			};
+			if data.len() != orig_length {
+				return Err(Error::IllFormed);
+			}
+
+			tables.push((tag, data));
+		}
+
+		Ok(build_sfnt(flavor, &tables))
+	}
+}
+
+// The 63 well-known WOFF2 table tags (WOFF2 spec, Table 7), indexed by the
+// 6-bit "known tag" field in the table directory.
+const WOFF2_KNOWN_TAGS: [&[u8; 4]; 63] = [
+	b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post",
+	b"cvt ", b"fpgm", b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT",
+	b"EBLC", b"gasp", b"hdmx", b"kern", b"LTSH", b"PCLT", b"VDMX", b"vhea",
+	b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC", b"JSTF", b"MATH",
+	b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+	b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar",
+	b"gvar", b"hsty", b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop",
+	b"trak", b"Zapf", b"Silf", b"Glat", b"Gloc", b"Feat", b"Sill",
+];
+
+#[cfg(feature = "woff2")]
+fn inflate_brotli(data: &[u8]) -> Result<Vec<u8>, Error> {
+	let mut out = Vec::new();
+	brotli_decompressor::BrotliDecompress(&mut &data[..], &mut out)
+		.map_err(|_| Error::IllFormed)?;
+	Ok(out)
+}
+
+// Reassembles a WOFF2 container into a plain SFNT byte buffer. Tables using
+// WOFF2's transform encodings (most notably the `glyf`/`loca` re-encoding
+// almost every subsetted web font uses) aren't reconstructed yet, and are
+// reported as `Error::UnsupportedCompression` rather than silently producing
+// a broken font.
+fn decompress_woff2(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+	#[cfg(not(feature = "woff2"))]
+	{
+		let _ = bytes;
+		Err(Error::UnsupportedCompression)
+	}
+
+	#[cfg(feature = "woff2")]
+	{
+		if bytes.len() < 48 {
+			return Err(Error::IllFormed);
+		}
+		let flavor = read_u32(bytes, 4);
+		let num_tables = read_u16(bytes, 12) as usize;
+		let total_compressed_size = read_u32(bytes, 20) as usize;
+
+		struct TableEntry {
+			tag: [u8; 4],
+			orig_length: usize,
+			transformed: bool,
+		}
+
+		let mut entries = Vec::with_capacity(num_tables);
+		let mut pos = 48;
+		for _ in 0..num_tables {
+			let flags = *bytes.get(pos).ok_or(Error::IllFormed)?;
+			pos += 1;
+			let tag_index = (flags & 0x3f) as usize;
+			let xform_version = (flags >> 6) & 0x3;
+
+			let tag = if tag_index == 63 {
+				let slice = bytes.get(pos..pos + 4).ok_or(Error::IllFormed)?;
+				pos += 4;
+				let mut arr = [0u8; 4];
+				arr.copy_from_slice(slice);
+				arr
+			} else {
+				*WOFF2_KNOWN_TAGS[tag_index]
+			};
+
+			let orig_length = read_uint_base128(bytes, &mut pos)? as usize;
+
+			// For `glyf`/`loca`, transform version 0 means "transformed"
+			// (3 means "not transformed"); for every other table, any
+			// nonzero version means "transformed".
+			let is_glyf_or_loca = &tag == b"glyf" || &tag == b"loca";
+			let transformed = if is_glyf_or_loca {
+				xform_version == 0
+			} else {
+				xform_version != 0
+			};
+			if transformed {
+				// Transformed tables carry a separate transformed length;
+				// we don't use it (see below), but still need to skip it.
+				read_uint_base128(bytes, &mut pos)?;
+			}
+
+			entries.push(TableEntry { tag, orig_length, transformed });
+		}
+
+		if entries.iter().any(|e| e.transformed) {
+			return Err(Error::UnsupportedCompression);
+		}
+
+		let compressed = bytes
+			.get(pos..pos + total_compressed_size)
+			.ok_or(Error::IllFormed)?;
+		let decompressed = inflate_brotli(compressed)?;
+
+		let mut tables = Vec::with_capacity(entries.len());
+		let mut offset = 0usize;
+		for entry in &entries {
+			let end = offset.checked_add(entry.orig_length).ok_or(Error::IllFormed)?;
+			let data = decompressed.get(offset..end).ok_or(Error::IllFormed)?.to_vec();
+			tables.push((entry.tag, data));
+			offset = end;
+		}
+
+		Ok(build_sfnt(flavor, &tables))
+	}
 }
 
 /// The type for errors returned by Fonterator.
@@ -527,6 +1806,11 @@ pub enum Error {
 	/// The caller tried to convert a `FontCollection` into a font via
 	/// `into_font`, but the `FontCollection` contains more than one font.
 	CollectionContainsMultipleFonts,
+
+	/// The font data was WOFF or WOFF2, but decompressing it needs a
+	/// feature that isn't enabled (`"woff"` or `"woff2"`), or uses a
+	/// compression scheme this crate doesn't support yet.
+	UnsupportedCompression,
 }
 
 impl fmt::Display for Error {
@@ -546,6 +1830,10 @@ impl std::error::Error for Error {
 				"Attempted to convert collection into a font, \
 				 but collection contais more than one font"
 			}
+			UnsupportedCompression => {
+				"Font data is WOFF/WOFF2-compressed in a way this build \
+				 of Fonterator can't decompress"
+			}
 		}
 	}
 }
@@ -555,3 +1843,661 @@ impl std::convert::From<Error> for std::io::Error {
 		std::io::Error::new(std::io::ErrorKind::Other, error)
 	}
 }
+
+#[cfg(test)]
+mod raster_tests {
+	use super::*;
+
+	// Integrates `accumulate_line`'s per-cell deltas into 0..=255 coverage,
+	// mirroring `Glyph::rasterize`'s row-by-row prefix sum.
+	fn integrate(accum: &[f32], width: usize, height: usize) -> Vec<u8> {
+		let mut bitmap = vec![0u8; width * height];
+		for row in 0..height {
+			let mut sum = 0.0f32;
+			let accum_row = &accum[row * (width + 1)..row * (width + 1) + width];
+			let bitmap_row = &mut bitmap[row * width..(row + 1) * width];
+			for (a, b) in accum_row.iter().zip(bitmap_row.iter_mut()) {
+				sum += *a;
+				*b = (sum.abs().min(1.0) * 255.0) as u8;
+			}
+		}
+		bitmap
+	}
+
+	#[test]
+	fn accumulate_line_fills_a_rectangle() {
+		// A 4x4 square, traced as four edges (two of them vertical, two
+		// horizontal), should rasterize to full coverage everywhere inside.
+		let width = 4;
+		let height = 4;
+		let lines = [
+			(Vec2(0.0, 0.0), Vec2(0.0, 4.0)),
+			(Vec2(0.0, 4.0), Vec2(4.0, 4.0)),
+			(Vec2(4.0, 4.0), Vec2(4.0, 0.0)),
+			(Vec2(4.0, 0.0), Vec2(0.0, 0.0)),
+		];
+		let mut accum = vec![0.0f32; (width + 1) * height];
+		for (p0, p1) in lines {
+			accumulate_line(&mut accum, width, height, p0, p1);
+		}
+		assert_eq!(integrate(&accum, width, height), vec![255u8; width * height]);
+	}
+
+	#[test]
+	fn accumulate_line_skips_horizontal_segments() {
+		let mut accum = vec![0.0f32; 5 * 4];
+		accumulate_line(&mut accum, 4, 4, Vec2(0.0, 1.0), Vec2(3.0, 1.0));
+		assert!(accum.iter().all(|&v| v == 0.0));
+	}
+
+	#[test]
+	fn flatten_quad_into_stays_near_the_control_polygon() {
+		let mut lines = Vec::new();
+		flatten_quad_into(Vec2(0.0, 0.0), Vec2(2.0, 4.0), Vec2(4.0, 0.0), &mut lines);
+		assert_eq!(lines.len(), RASTER_QUAD_SEGMENTS);
+		assert_eq!(lines.first().unwrap().0 .0, 0.0);
+		assert_eq!(lines.last().unwrap().1 .0, 4.0);
+		for (_, p) in &lines {
+			assert!(p.1 <= 4.0 + 1e-4);
+		}
+	}
+}
+
+#[cfg(test)]
+mod cubic_tests {
+	use super::*;
+
+	#[test]
+	fn into_quadratic_passes_through_non_cubic_ops() {
+		let path = Path(vec![
+			PathOp::MoveTo(0.0, 0.0),
+			PathOp::LineTo(1.0, 0.0),
+			PathOp::QuadTo(1.0, 1.0, 0.5, 0.5),
+			PathOp::LineClose,
+		]);
+		let ops: Vec<_> = path.into_quadratic(0.1).into_iter().collect();
+		assert_eq!(ops.len(), 4);
+	}
+
+	#[test]
+	fn into_quadratic_flattens_a_colinear_curve_to_a_single_quad() {
+		let path = Path(vec![
+			PathOp::MoveTo(0.0, 0.0),
+			PathOp::CurveTo(3.0, 0.0, 1.0, 0.0, 2.0, 0.0),
+			PathOp::LineClose,
+		]);
+		let ops: Vec<_> = path.into_quadratic(0.01).into_iter().collect();
+		assert_eq!(ops.len(), 3);
+		match ops[1] {
+			PathOp::QuadTo(x, y, cx, cy) => {
+				assert!((x - 3.0).abs() < 1e-4 && y.abs() < 1e-4);
+				assert!((cx - 1.5).abs() < 1e-4 && cy.abs() < 1e-4);
+			}
+			_ => panic!("expected a single flattened QuadTo"),
+		}
+		assert!(matches!(ops[2], PathOp::LineClose));
+	}
+
+	#[test]
+	fn into_quadratic_flattens_a_colinear_curve_close_to_a_single_quad_close() {
+		let path = Path(vec![
+			PathOp::MoveTo(0.0, 0.0),
+			PathOp::LineTo(3.0, 0.0),
+			PathOp::CurveClose(2.0, 0.0, 1.0, 0.0),
+		]);
+		let ops: Vec<_> = path.into_quadratic(0.01).into_iter().collect();
+		assert_eq!(ops.len(), 3);
+		match ops[2] {
+			PathOp::QuadClose(cx, cy) => {
+				assert!((cx - 1.5).abs() < 1e-4 && cy.abs() < 1e-4);
+			}
+			_ => panic!("expected a single flattened QuadClose"),
+		}
+	}
+
+	#[test]
+	fn point_line_distance_handles_degenerate_segments() {
+		let p = Vec2(1.0, 1.0);
+		let a = Vec2(0.0, 0.0);
+		assert!((point_line_distance(p, a, a) - 2f32.sqrt()).abs() < 1e-4);
+	}
+}
+
+#[cfg(test)]
+mod woff_tests {
+	use super::*;
+
+	#[test]
+	fn build_sfnt_sorts_tables_and_pads_to_four_bytes() {
+		let tables = vec![
+			(*b"name", vec![1u8, 2, 3]),
+			(*b"head", vec![4u8, 5, 6, 7]),
+		];
+		let sfnt = build_sfnt(0x0001_0000, &tables);
+
+		assert_eq!(read_u32(&sfnt, 0), 0x0001_0000);
+		assert_eq!(read_u16(&sfnt, 4), 2);
+
+		// Table directory entries must come back out sorted by tag, so
+		// "head" (< "name") is first even though "name" was pushed first.
+		let head_off = find_table_offset(&sfnt, 0, b"head").unwrap() as usize;
+		let name_off = find_table_offset(&sfnt, 0, b"name").unwrap() as usize;
+		assert!(head_off < name_off);
+		assert_eq!(&sfnt[head_off..head_off + 4], &[4, 5, 6, 7]);
+		assert_eq!(&sfnt[name_off..name_off + 3], &[1, 2, 3]);
+		// Each table's data is padded out to a 4-byte boundary.
+		assert_eq!(name_off % 4, 0);
+	}
+
+	#[cfg(feature = "woff")]
+	#[test]
+	fn decompress_woff_reassembles_stored_tables() {
+		let head_data = [4u8, 5, 6, 7];
+		let name_data = [1u8, 2, 3, 4, 5];
+
+		let mut woff = vec![0u8; 44];
+		woff[0..4].copy_from_slice(&WOFF_MAGIC.to_be_bytes());
+		woff[4..8].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+		woff[12..14].copy_from_slice(&2u16.to_be_bytes());
+
+		let mut entries = Vec::new();
+		let mut data = Vec::new();
+		for (tag, bytes) in [(b"head", &head_data[..]), (b"name", &name_data[..])] {
+			entries.extend_from_slice(tag);
+			entries.extend_from_slice(&(44 + 40 + data.len() as u32).to_be_bytes());
+			entries.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+			entries.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+			entries.extend_from_slice(&0u32.to_be_bytes());
+			data.extend_from_slice(bytes);
+		}
+		woff.extend_from_slice(&entries);
+		woff.extend_from_slice(&data);
+
+		let sfnt = decompress_woff(&woff).unwrap();
+		let head_off = find_table_offset(&sfnt, 0, b"head").unwrap() as usize;
+		let name_off = find_table_offset(&sfnt, 0, b"name").unwrap() as usize;
+		assert_eq!(&sfnt[head_off..head_off + 4], &head_data);
+		assert_eq!(&sfnt[name_off..name_off + 5], &name_data);
+	}
+
+	#[cfg(feature = "woff2")]
+	#[test]
+	fn decompress_woff2_reassembles_stored_tables() {
+		fn write_uint_base128(out: &mut Vec<u8>, mut value: u32) {
+			let mut bytes = [0u8; 5];
+			let mut n = 0;
+			loop {
+				bytes[n] = (value & 0x7f) as u8;
+				n += 1;
+				if value < 0x80 {
+					break;
+				}
+				value >>= 7;
+			}
+			for i in (0..n).rev() {
+				let continuation = if i == 0 { 0 } else { 0x80 };
+				out.push(bytes[i] | continuation);
+			}
+		}
+
+		let head_data = [4u8, 5, 6, 7];
+		let name_data = [1u8, 2, 3, 4, 5];
+		let head_tag_index = WOFF2_KNOWN_TAGS.iter().position(|t| *t == b"head").unwrap() as u8;
+		let name_tag_index = WOFF2_KNOWN_TAGS.iter().position(|t| *t == b"name").unwrap() as u8;
+
+		let mut raw = Vec::new();
+		raw.extend_from_slice(&head_data);
+		raw.extend_from_slice(&name_data);
+
+		let mut compressed = Vec::new();
+		{
+			let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 9, 22);
+			use std::io::Write;
+			writer.write_all(&raw).unwrap();
+		}
+
+		let mut entries = Vec::new();
+		entries.push(head_tag_index);
+		write_uint_base128(&mut entries, head_data.len() as u32);
+		entries.push(name_tag_index);
+		write_uint_base128(&mut entries, name_data.len() as u32);
+
+		let mut woff2 = vec![0u8; 48];
+		woff2[0..4].copy_from_slice(&WOFF2_MAGIC.to_be_bytes());
+		woff2[4..8].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+		woff2[12..14].copy_from_slice(&2u16.to_be_bytes());
+		woff2[20..24].copy_from_slice(&(compressed.len() as u32).to_be_bytes());
+		woff2.extend_from_slice(&entries);
+		woff2.extend_from_slice(&compressed);
+
+		let sfnt = decompress_woff2(&woff2).unwrap();
+		let head_off = find_table_offset(&sfnt, 0, b"head").unwrap() as usize;
+		let name_off = find_table_offset(&sfnt, 0, b"name").unwrap() as usize;
+		assert_eq!(&sfnt[head_off..head_off + 4], &head_data);
+		assert_eq!(&sfnt[name_off..name_off + 5], &name_data);
+	}
+}
+
+#[cfg(test)]
+mod gsub_tests {
+	use super::*;
+
+	#[test]
+	fn coverage_index_format1_finds_listed_glyphs() {
+		let mut data = Vec::new();
+		data.extend_from_slice(&1u16.to_be_bytes()); // format 1
+		data.extend_from_slice(&3u16.to_be_bytes()); // glyphCount
+		for g in [5u16, 10, 20] {
+			data.extend_from_slice(&g.to_be_bytes());
+		}
+		assert_eq!(gsub_coverage_index(&data, 0, 10), Some(1));
+		assert_eq!(gsub_coverage_index(&data, 0, 20), Some(2));
+		assert_eq!(gsub_coverage_index(&data, 0, 7), None);
+	}
+
+	#[test]
+	fn coverage_index_format2_finds_glyphs_in_ranges() {
+		let mut data = Vec::new();
+		data.extend_from_slice(&2u16.to_be_bytes()); // format 2
+		data.extend_from_slice(&2u16.to_be_bytes()); // rangeCount
+		for (start, end, idx) in [(10u16, 20u16, 0u16), (30, 30, 11)] {
+			data.extend_from_slice(&start.to_be_bytes());
+			data.extend_from_slice(&end.to_be_bytes());
+			data.extend_from_slice(&idx.to_be_bytes());
+		}
+		assert_eq!(gsub_coverage_index(&data, 0, 15), Some(5));
+		assert_eq!(gsub_coverage_index(&data, 0, 30), Some(11));
+		assert_eq!(gsub_coverage_index(&data, 0, 25), None);
+	}
+
+	// Builds a coverage table (format 1) covering exactly `glyphs`, appended
+	// at the current end of `data`, and returns its offset.
+	fn push_coverage(data: &mut Vec<u8>, glyphs: &[u16]) -> usize {
+		let off = data.len();
+		data.extend_from_slice(&1u16.to_be_bytes());
+		data.extend_from_slice(&(glyphs.len() as u16).to_be_bytes());
+		for &g in glyphs {
+			data.extend_from_slice(&g.to_be_bytes());
+		}
+		off
+	}
+
+	#[test]
+	fn single_subst_format1_applies_a_glyph_id_delta() {
+		let mut data = Vec::new();
+		let sub_off = data.len();
+		data.extend_from_slice(&1u16.to_be_bytes()); // substFormat
+		data.extend_from_slice(&0u16.to_be_bytes()); // coverageOffset, patched below
+		data.extend_from_slice(&5i16.to_be_bytes()); // deltaGlyphID
+		let cov_off = push_coverage(&mut data, &[100]);
+		let rel = (cov_off - sub_off) as u16;
+		data[sub_off + 2..sub_off + 4].copy_from_slice(&rel.to_be_bytes());
+
+		assert_eq!(gsub_single_subst(&data, sub_off, 100, 1000), Some(105));
+		assert_eq!(gsub_single_subst(&data, sub_off, 101, 1000), None);
+	}
+
+	#[test]
+	fn single_subst_format1_rejects_a_delta_that_overflows_glyph_count() {
+		let mut data = Vec::new();
+		let sub_off = data.len();
+		data.extend_from_slice(&1u16.to_be_bytes()); // substFormat
+		data.extend_from_slice(&0u16.to_be_bytes()); // coverageOffset, patched below
+		data.extend_from_slice(&5i16.to_be_bytes()); // deltaGlyphID
+		let cov_off = push_coverage(&mut data, &[100]);
+		let rel = (cov_off - sub_off) as u16;
+		data[sub_off + 2..sub_off + 4].copy_from_slice(&rel.to_be_bytes());
+
+		// deltaGlyphID of 5 puts the replacement (105) past a font that only
+		// has 105 glyphs (valid ids 0..105); a font this size can't use it.
+		assert_eq!(gsub_single_subst(&data, sub_off, 100, 105), None);
+		assert_eq!(gsub_single_subst(&data, sub_off, 100, 106), Some(105));
+	}
+
+	#[test]
+	fn single_subst_format2_applies_an_explicit_substitute_list() {
+		let mut data = Vec::new();
+		let sub_off = data.len();
+		data.extend_from_slice(&2u16.to_be_bytes()); // substFormat
+		data.extend_from_slice(&0u16.to_be_bytes()); // coverageOffset, patched below
+		data.extend_from_slice(&2u16.to_be_bytes()); // glyphCount
+		for g in [201u16, 202] {
+			data.extend_from_slice(&g.to_be_bytes());
+		}
+		let cov_off = push_coverage(&mut data, &[100, 101]);
+		let rel = (cov_off - sub_off) as u16;
+		data[sub_off + 2..sub_off + 4].copy_from_slice(&rel.to_be_bytes());
+
+		assert_eq!(gsub_single_subst(&data, sub_off, 100, 1000), Some(201));
+		assert_eq!(gsub_single_subst(&data, sub_off, 101, 1000), Some(202));
+	}
+
+	#[test]
+	fn ligature_subst_matches_the_full_component_sequence() {
+		let mut data = Vec::new();
+		let sub_off = data.len();
+		data.extend_from_slice(&1u16.to_be_bytes()); // substFormat
+		data.extend_from_slice(&0u16.to_be_bytes()); // coverageOffset, patched below
+		data.extend_from_slice(&1u16.to_be_bytes()); // ligSetCount
+		data.extend_from_slice(&0u16.to_be_bytes()); // ligSetOffsets[0], patched below
+
+		let ligset_off = data.len();
+		data.extend_from_slice(&1u16.to_be_bytes()); // ligatureCount
+		data.extend_from_slice(&0u16.to_be_bytes()); // ligatureOffsets[0], patched below
+
+		let lig_off = data.len();
+		data.extend_from_slice(&999u16.to_be_bytes()); // ligatureGlyph
+		data.extend_from_slice(&3u16.to_be_bytes()); // componentCount
+		for c in [60u16, 70] {
+			data.extend_from_slice(&c.to_be_bytes());
+		}
+
+		let cov_off = push_coverage(&mut data, &[50]);
+		let rel = (cov_off - sub_off) as u16;
+		data[sub_off + 2..sub_off + 4].copy_from_slice(&rel.to_be_bytes());
+		let ligset_rel = (ligset_off - sub_off) as u16;
+		data[sub_off + 6..sub_off + 8].copy_from_slice(&ligset_rel.to_be_bytes());
+		let lig_rel = (lig_off - ligset_off) as u16;
+		data[ligset_off + 2..ligset_off + 4].copy_from_slice(&lig_rel.to_be_bytes());
+
+		assert_eq!(gsub_ligature_subst(&data, sub_off, &[50, 60, 70, 80], 1000), Some((999, 3)));
+		assert_eq!(gsub_ligature_subst(&data, sub_off, &[50, 60, 99], 1000), None);
+		assert_eq!(gsub_ligature_subst(&data, sub_off, &[50], 1000), None);
+		assert_eq!(gsub_ligature_subst(&data, sub_off, &[1, 2, 3], 1000), None);
+		// `999` is the ligature glyph; a font with only 999 glyphs (valid
+		// ids 0..999) can't use it.
+		assert_eq!(gsub_ligature_subst(&data, sub_off, &[50, 60, 70, 80], 999), None);
+	}
+
+	// Builds a `ScriptList` with one script per given tag,
+	// each with an empty default `LangSys` (no required feature, no
+	// explicit feature indices), and returns (data, script_list_off).
+	fn build_script_list(scripts: &[&[u8; 4]]) -> (Vec<u8>, usize) {
+		let mut data = Vec::new();
+		let script_list_off = data.len();
+		data.extend_from_slice(&(scripts.len() as u16).to_be_bytes());
+		let records_end = data.len() + 6 * scripts.len();
+		for _ in scripts {
+			data.extend_from_slice(&[0u8; 6]);
+		}
+		for (i, tag) in scripts.iter().enumerate() {
+			let script_off = data.len();
+			// DefaultLangSys offset, LangSysCount
+			data.extend_from_slice(&4u16.to_be_bytes());
+			data.extend_from_slice(&0u16.to_be_bytes());
+			// DefaultLangSys table: lookupOrder(unused)=0, requiredFeatureIndex=0xFFFF, featureCount=0
+			data.extend_from_slice(&0u16.to_be_bytes());
+			data.extend_from_slice(&0xFFFFu16.to_be_bytes());
+			data.extend_from_slice(&0u16.to_be_bytes());
+
+			let rec = script_list_off + 2 + 6 * i;
+			data[rec..rec + 4].copy_from_slice(&tag[..]);
+			let rel = (script_off - script_list_off) as u16;
+			data[rec + 4..rec + 6].copy_from_slice(&rel.to_be_bytes());
+		}
+		let _ = records_end;
+		(data, script_list_off)
+	}
+
+	#[test]
+	fn find_script_prefers_dflt_even_if_not_first() {
+		let (data, script_list_off) = build_script_list(&[b"latn", b"DFLT"]);
+		let dflt_off = gsub_find_script(&data, script_list_off).unwrap();
+		let expected_off = gsub_default_langsys(&data, dflt_off).unwrap();
+		// `DFLT`'s script is the second record; make sure we didn't just
+		// grab the first one.
+		let latn_script_off = script_list_off + try_read_u16(&data, script_list_off + 2 + 4).unwrap() as usize;
+		assert_ne!(dflt_off, latn_script_off);
+		assert!(gsub_default_langsys(&data, dflt_off) == Some(expected_off));
+	}
+
+	#[test]
+	fn find_script_falls_back_to_first_script_without_dflt() {
+		let (data, script_list_off) = build_script_list(&[b"latn", b"grek"]);
+		let latn_script_off = script_list_off + try_read_u16(&data, script_list_off + 2 + 4).unwrap() as usize;
+		assert_eq!(gsub_find_script(&data, script_list_off), Some(latn_script_off));
+	}
+
+	#[test]
+	fn default_langsys_prefers_default_over_explicit_records() {
+		// defaultLangSysOffset = 10 (nonzero), langSysCount = 1, with one
+		// LangSysRecord (tag + offset 42) at script_off + 4 that must be
+		// ignored.
+		let mut data = vec![0u8; 4 + 6];
+		data[0..2].copy_from_slice(&10u16.to_be_bytes());
+		data[2..4].copy_from_slice(&1u16.to_be_bytes());
+		data[4..8].copy_from_slice(b"xxxx");
+		data[8..10].copy_from_slice(&42u16.to_be_bytes());
+		assert_eq!(gsub_default_langsys(&data, 0), Some(10));
+	}
+
+	#[test]
+	fn default_langsys_falls_back_to_first_explicit_record() {
+		// defaultLangSysOffset = 0, langSysCount = 1, one LangSysRecord
+		// (tag + offset) at script_off + 4.
+		let mut data = vec![0u8; 4 + 6];
+		data[2..4].copy_from_slice(&1u16.to_be_bytes());
+		data[4..8].copy_from_slice(b"xxxx");
+		data[8..10].copy_from_slice(&42u16.to_be_bytes());
+		assert_eq!(gsub_default_langsys(&data, 0), Some(42));
+	}
+
+	#[test]
+	fn default_langsys_is_none_with_no_default_and_no_records() {
+		let data = vec![0u8; 4];
+		assert_eq!(gsub_default_langsys(&data, 0), None);
+	}
+
+	// Builds a `FeatureList` out of `(tag, lookup indices)` pairs and a
+	// `LangSys` referencing all of them (as explicit, non-required
+	// features), returning (data, feature_list_off, langsys_off).
+	fn build_features_and_langsys(features: &[(&[u8; 4], &[u16])]) -> (Vec<u8>, usize, usize) {
+		let mut data = Vec::new();
+		let feature_list_off = data.len();
+		data.extend_from_slice(&(features.len() as u16).to_be_bytes());
+		for _ in features {
+			data.extend_from_slice(&[0u8; 6]);
+		}
+		for (i, (tag, lookups)) in features.iter().enumerate() {
+			let feature_off = data.len();
+			data.extend_from_slice(&0u16.to_be_bytes()); // featureParams
+			data.extend_from_slice(&(lookups.len() as u16).to_be_bytes());
+			for &l in *lookups {
+				data.extend_from_slice(&l.to_be_bytes());
+			}
+			let rec = feature_list_off + 2 + 6 * i;
+			data[rec..rec + 4].copy_from_slice(&tag[..]);
+			let rel = (feature_off - feature_list_off) as u16;
+			data[rec + 4..rec + 6].copy_from_slice(&rel.to_be_bytes());
+		}
+
+		let langsys_off = data.len();
+		data.extend_from_slice(&0u16.to_be_bytes()); // lookupOrder (unused)
+		data.extend_from_slice(&0xFFFFu16.to_be_bytes()); // requiredFeatureIndex: none
+		data.extend_from_slice(&(features.len() as u16).to_be_bytes());
+		for i in 0..features.len() as u16 {
+			data.extend_from_slice(&i.to_be_bytes());
+		}
+
+		(data, feature_list_off, langsys_off)
+	}
+
+	#[test]
+	fn lookup_indices_skips_optional_features_and_sorts_ascending() {
+		let (data, feature_list_off, langsys_off) = build_features_and_langsys(&[
+			(b"dlig", &[5]),       // optional: discretionary ligatures, not applied
+			(b"liga", &[10, 3]),   // default-on, declared out of lookup-index order
+			(b"calt", &[1]),       // default-on
+		]);
+
+		let lookups = gsub_lookup_indices(&data, feature_list_off, langsys_off).unwrap();
+		assert_eq!(lookups, vec![1, 3, 10]);
+	}
+
+	#[test]
+	fn lookup_indices_dedupes_shared_lookups() {
+		let (data, feature_list_off, langsys_off) = build_features_and_langsys(&[
+			(b"liga", &[3]),
+			(b"calt", &[3, 1]),
+		]);
+
+		let lookups = gsub_lookup_indices(&data, feature_list_off, langsys_off).unwrap();
+		assert_eq!(lookups, vec![1, 3]);
+	}
+
+	// Builds a minimal SFNT that `tt::FontInfo::new` can parse: a `cmap`
+	// mapping a single codepoint to `glyph`, just enough `head`/`hhea`/
+	// `hmtx`/`maxp`/`loca`/`glyf` for the font to report `num_glyphs` glyphs,
+	// and `gsub`, if given, as its `GSUB` table. None of the glyph outlines
+	// matter here, only glyph-id bookkeeping, so `loca`/`glyf` are left empty.
+	fn build_minimal_font(num_glyphs: u16, codepoint: u32, glyph: u16, gsub: Option<Vec<u8>>) -> Vec<u8> {
+		let mut head = vec![0u8; 54];
+		head[18..20].copy_from_slice(&1000u16.to_be_bytes()); // unitsPerEm
+		// indexToLocFormat @ 50 left at 0 (short)
+
+		let mut hhea = vec![0u8; 36];
+		hhea[4..6].copy_from_slice(&800i16.to_be_bytes()); // ascent
+		hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes()); // descent
+		hhea[34..36].copy_from_slice(&num_glyphs.to_be_bytes()); // numOfLongHorMetrics
+
+		let mut hmtx = Vec::new();
+		for _ in 0..num_glyphs {
+			hmtx.extend_from_slice(&500u16.to_be_bytes()); // advanceWidth
+			hmtx.extend_from_slice(&0i16.to_be_bytes()); // lsb
+		}
+
+		let mut maxp = vec![0u8; 6];
+		maxp[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+		maxp[4..6].copy_from_slice(&num_glyphs.to_be_bytes());
+
+		// Short loca: (num_glyphs + 1) u16 offsets, all pointing at an empty
+		// `glyf` table.
+		let loca = vec![0u8; 2 * (num_glyphs as usize + 1)];
+		let glyf = Vec::new();
+
+		// `cmap` subtable format 6 (trimmed table mapping): one codepoint to
+		// one glyph, reached through a Microsoft/UnicodeBMP encoding record
+		// so `find_glyph_index` picks it up.
+		let mut subtable = Vec::new();
+		subtable.extend_from_slice(&6u16.to_be_bytes()); // format
+		subtable.extend_from_slice(&0u16.to_be_bytes()); // length, unused by stb_truetype
+		subtable.extend_from_slice(&0u16.to_be_bytes()); // language
+		subtable.extend_from_slice(&(codepoint as u16).to_be_bytes()); // firstCode
+		subtable.extend_from_slice(&1u16.to_be_bytes()); // entryCount
+		subtable.extend_from_slice(&glyph.to_be_bytes());
+
+		let mut cmap = Vec::new();
+		cmap.extend_from_slice(&0u16.to_be_bytes()); // version
+		cmap.extend_from_slice(&1u16.to_be_bytes()); // numTables
+		cmap.extend_from_slice(&3u16.to_be_bytes()); // platformID: Microsoft
+		cmap.extend_from_slice(&1u16.to_be_bytes()); // encodingID: UnicodeBMP
+		cmap.extend_from_slice(&12u32.to_be_bytes()); // offset to subtable
+		cmap.extend_from_slice(&subtable);
+
+		let mut tables = vec![
+			(*b"cmap", cmap),
+			(*b"glyf", glyf),
+			(*b"head", head),
+			(*b"hhea", hhea),
+			(*b"hmtx", hmtx),
+			(*b"loca", loca),
+			(*b"maxp", maxp),
+		];
+		if let Some(gsub) = gsub {
+			tables.push((*b"GSUB", gsub));
+		}
+		build_sfnt(0x0001_0000, &tables)
+	}
+
+	// Builds a `GSUB` table whose `DFLT` script's default langsys has a
+	// single default-on `liga` feature, pointing at a single lookup (type 1,
+	// single substitution) that replaces `covered_glyph` with
+	// `covered_glyph + delta`.
+	fn build_gsub_with_single_subst(covered_glyph: u16, delta: i16) -> Vec<u8> {
+		// SingleSubst format 1, with its Coverage table appended right after.
+		let mut subst = Vec::new();
+		subst.extend_from_slice(&1u16.to_be_bytes()); // substFormat
+		subst.extend_from_slice(&0u16.to_be_bytes()); // coverageOffset, patched below
+		subst.extend_from_slice(&delta.to_be_bytes()); // deltaGlyphID
+		let cov_off = subst.len();
+		subst.extend_from_slice(&1u16.to_be_bytes()); // coverage format 1
+		subst.extend_from_slice(&1u16.to_be_bytes()); // glyphCount
+		subst.extend_from_slice(&covered_glyph.to_be_bytes());
+		subst[2..4].copy_from_slice(&(cov_off as u16).to_be_bytes());
+
+		// Lookup table: type 1, one subtable.
+		let mut lookup = Vec::new();
+		lookup.extend_from_slice(&1u16.to_be_bytes()); // lookupType
+		lookup.extend_from_slice(&0u16.to_be_bytes()); // lookupFlag
+		lookup.extend_from_slice(&1u16.to_be_bytes()); // subTableCount
+		lookup.extend_from_slice(&8u16.to_be_bytes()); // subtable offset (right after this header)
+		lookup.extend_from_slice(&subst);
+
+		// LookupList: one lookup.
+		let mut lookup_list = Vec::new();
+		lookup_list.extend_from_slice(&1u16.to_be_bytes()); // lookupCount
+		lookup_list.extend_from_slice(&4u16.to_be_bytes()); // lookup offset (right after this header)
+		lookup_list.extend_from_slice(&lookup);
+
+		// Feature table: no params, one lookup index (0).
+		let mut feature = Vec::new();
+		feature.extend_from_slice(&0u16.to_be_bytes()); // featureParams
+		feature.extend_from_slice(&1u16.to_be_bytes()); // lookupCount
+		feature.extend_from_slice(&0u16.to_be_bytes()); // lookupListIndex[0]
+
+		// FeatureList: one feature, tagged "liga".
+		let mut feature_list = Vec::new();
+		feature_list.extend_from_slice(&1u16.to_be_bytes()); // featureCount
+		feature_list.extend_from_slice(b"liga");
+		feature_list.extend_from_slice(&8u16.to_be_bytes()); // feature offset (right after this record)
+		feature_list.extend_from_slice(&feature);
+
+		// LangSys: no required feature, one feature index (0).
+		let mut langsys = Vec::new();
+		langsys.extend_from_slice(&0u16.to_be_bytes()); // lookupOrder (unused)
+		langsys.extend_from_slice(&0xFFFFu16.to_be_bytes()); // requiredFeatureIndex: none
+		langsys.extend_from_slice(&1u16.to_be_bytes()); // featureCount
+		langsys.extend_from_slice(&0u16.to_be_bytes()); // featureIndex[0]
+
+		// Script: defaultLangSysOffset right after, no explicit LangSysRecords.
+		let mut script = Vec::new();
+		script.extend_from_slice(&4u16.to_be_bytes()); // defaultLangSysOffset
+		script.extend_from_slice(&0u16.to_be_bytes()); // langSysCount
+		script.extend_from_slice(&langsys);
+
+		// ScriptList: one script, tagged "DFLT".
+		let mut script_list = Vec::new();
+		script_list.extend_from_slice(&1u16.to_be_bytes()); // scriptCount
+		script_list.extend_from_slice(b"DFLT");
+		script_list.extend_from_slice(&8u16.to_be_bytes()); // script offset (right after this record)
+		script_list.extend_from_slice(&script);
+
+		let mut gsub = Vec::new();
+		gsub.extend_from_slice(&0x0001_0000u32.to_be_bytes()); // version
+		gsub.extend_from_slice(&10u16.to_be_bytes()); // scriptListOffset, right after this header
+		let feature_list_off = 10 + script_list.len() as u16;
+		gsub.extend_from_slice(&feature_list_off.to_be_bytes());
+		let lookup_list_off = feature_list_off + feature_list.len() as u16;
+		gsub.extend_from_slice(&lookup_list_off.to_be_bytes());
+		gsub.extend_from_slice(&script_list);
+		gsub.extend_from_slice(&feature_list);
+		gsub.extend_from_slice(&lookup_list);
+
+		gsub
+	}
+
+	#[test]
+	fn shape_skips_a_single_substitution_that_would_produce_an_out_of_range_glyph() {
+		// Glyph 36 ("A" in this synthetic font) has a `liga`-feature single
+		// substitution wired up that would replace it with glyph 46 — but
+		// this font only has 40 glyphs (valid ids 0..40). `Font::shape`
+		// must not pass that bogus id on to `glyph()`, which asserts every
+		// `GlyphId` it's given is in range, and must not panic itself.
+		let gsub = build_gsub_with_single_subst(36, 10);
+		let font_data = build_minimal_font(40, 'A' as u32, 36, Some(gsub));
+
+		let font = FontCollection::new(font_data).unwrap().into_font().unwrap();
+		let glyphs: Vec<u16> = font.shape("A", (16.0, 16.0)).map(|(g, _)| g.id().0 as u16).collect();
+		assert_eq!(glyphs, vec![36]);
+	}
+}